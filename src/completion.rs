@@ -0,0 +1,191 @@
+// Copyright © 2015-2016 - Samuel Dolt <samuel@dolt.ch>
+//
+// See the COPYRIGHT file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use Command;
+
+/// The shell a completion script is generated for
+pub enum Shell {
+    /// Bash
+    Bash,
+    /// Zsh
+    Zsh,
+    /// Fish
+    Fish,
+}
+
+/// Escape a single quote for interpolation into a POSIX-style single-quoted
+/// shell string, the way zsh (and sh) expect it: close the quote, emit an
+/// escaped quote, then reopen it.
+fn escape_sq(text: &str) -> String {
+    text.replace('\'', "'\\''")
+}
+
+/// Escape a single quote for interpolation into a fish single-quoted
+/// string, where `\'` is the literal escape fish itself understands.
+fn escape_fish_sq(text: &str) -> String {
+    text.replace('\'', "\\'")
+}
+
+/// Generate a completion script for `shell`, listing `commands` as the
+/// completable words after `program_name`.
+pub fn generate(program_name: &str, commands: &[Box<Command>], shell: Shell) -> String {
+    match shell {
+        Shell::Bash => generate_bash(program_name, commands),
+        Shell::Zsh => generate_zsh(program_name, commands),
+        Shell::Fish => generate_fish(program_name, commands),
+    }
+}
+
+fn generate_bash(program_name: &str, commands: &[Box<Command>]) -> String {
+    let names: Vec<&str> = commands.iter().map(|cmd| cmd.name()).collect();
+    let names_list = names.join(" ");
+
+    let mut top_level = names.clone();
+    top_level.push("help");
+    let top_level_list = top_level.join(" ");
+
+    let mut out = String::with_capacity(250);
+    out.push_str(&format!("_{}() {{\n", program_name));
+    out.push_str("    local cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    out.push_str("    local prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+    out.push_str("    if [ \"$COMP_CWORD\" -eq 1 ]; then\n");
+    out.push_str(&format!("        COMPREPLY=( $(compgen -W \"{}\" -- \"$cur\") )\n",
+                          top_level_list));
+    out.push_str("    elif [ \"$COMP_CWORD\" -eq 2 ] && [ \"$prev\" = \"help\" ]; then\n");
+    out.push_str(&format!("        COMPREPLY=( $(compgen -W \"{}\" -- \"$cur\") )\n", names_list));
+    out.push_str("    fi\n");
+    out.push_str("}\n");
+    out.push_str(&format!("complete -F _{name} {name}\n", name = program_name));
+
+    out
+}
+
+fn generate_zsh(program_name: &str, commands: &[Box<Command>]) -> String {
+    let mut out = String::with_capacity(250);
+    out.push_str(&format!("#compdef {}\n\n", program_name));
+    out.push_str(&format!("_{}() {{\n", program_name));
+    out.push_str("    local -a commands\n");
+    out.push_str("    commands=(\n");
+    for cmd in commands.iter() {
+        out.push_str(&format!("        '{}:{}'\n",
+                              escape_sq(cmd.name()),
+                              escape_sq(cmd.description())));
+    }
+    out.push_str("        'help:Show help for a command'\n");
+    out.push_str("    )\n");
+    out.push_str("    _describe 'command' commands\n");
+    out.push_str("}\n\n");
+    out.push_str(&format!("_{}\n", program_name));
+
+    out
+}
+
+fn generate_fish(program_name: &str, commands: &[Box<Command>]) -> String {
+    let mut out = String::with_capacity(250);
+    for cmd in commands.iter() {
+        out.push_str(&format!("complete -c {} -n '__fish_use_subcommand' -a {} -d '{}'\n",
+                              program_name,
+                              cmd.name(),
+                              escape_fish_sq(cmd.description())));
+    }
+    out.push_str(&format!("complete -c {} -n '__fish_use_subcommand' -a help -d 'Show help \
+                            for a command'\n",
+                          program_name));
+
+    let names: Vec<&str> = commands.iter().map(|cmd| cmd.name()).collect();
+    out.push_str(&format!("complete -c {} -n '__fish_seen_subcommand_from help' -a \"{}\"\n",
+                          program_name,
+                          names.join(" ")));
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Command;
+
+    struct CmdA;
+
+    impl Command for CmdA {
+        fn name<'a>(&self) -> &'a str {
+            "cmd-a"
+        }
+        fn help<'a>(&self) -> &'a str {
+            "HELP"
+        }
+        fn description<'a>(&self) -> &'a str {
+            "DESCR"
+        }
+        fn run(&self, argv: &Vec<String>) -> Result<(), Box<::std::error::Error>> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_generate_bash() {
+        let commands: Vec<Box<Command>> = vec![Box::new(CmdA)];
+        let out = generate("bin", &commands, Shell::Bash);
+
+        assert!(out.contains("complete -F _bin bin"));
+        assert!(out.contains("cmd-a help"));
+    }
+
+    #[test]
+    fn test_generate_zsh() {
+        let commands: Vec<Box<Command>> = vec![Box::new(CmdA)];
+        let out = generate("bin", &commands, Shell::Zsh);
+
+        assert!(out.contains("#compdef bin"));
+        assert!(out.contains("'cmd-a:DESCR'"));
+    }
+
+    #[test]
+    fn test_generate_fish() {
+        let commands: Vec<Box<Command>> = vec![Box::new(CmdA)];
+        let out = generate("bin", &commands, Shell::Fish);
+
+        assert!(out.contains("-a cmd-a -d 'DESCR'"));
+        assert!(out.contains("__fish_seen_subcommand_from help"));
+    }
+
+    struct CmdWithQuote;
+
+    impl Command for CmdWithQuote {
+        fn name<'a>(&self) -> &'a str {
+            "cmd-b"
+        }
+        fn help<'a>(&self) -> &'a str {
+            "HELP"
+        }
+        fn description<'a>(&self) -> &'a str {
+            "Don't break things"
+        }
+        fn run(&self, argv: &Vec<String>) -> Result<(), Box<::std::error::Error>> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_generate_zsh_escapes_apostrophe() {
+        let commands: Vec<Box<Command>> = vec![Box::new(CmdWithQuote)];
+        let out = generate("bin", &commands, Shell::Zsh);
+
+        assert!(out.contains("'cmd-b:Don'\\''t break things'"));
+    }
+
+    #[test]
+    fn test_generate_fish_escapes_apostrophe() {
+        let commands: Vec<Box<Command>> = vec![Box::new(CmdWithQuote)];
+        let out = generate("bin", &commands, Shell::Fish);
+
+        assert!(out.contains("-d 'Don\\'t break things'"));
+    }
+}
@@ -0,0 +1,155 @@
+// Copyright © 2015-2016 - Samuel Dolt <samuel@dolt.ch>
+//
+// See the COPYRIGHT file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use ansi_term::Colour::{Red, Cyan};
+use std::io::IsTerminal;
+
+/// Whether a `CmdHandler` should colorize its output
+#[derive(Clone, Copy)]
+pub enum ColorChoice {
+    /// Always colorize, even if stdout is not a TTY
+    Always,
+    /// Never colorize
+    Never,
+    /// Colorize only when stdout is connected to a TTY
+    Auto,
+}
+
+impl ColorChoice {
+    /// Return true if output should be colorized
+    pub fn enabled(&self) -> bool {
+        match *self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => ::std::io::stdout().is_terminal(),
+        }
+    }
+}
+
+/// Severity of a `Diagnostic`
+pub enum Severity {
+    /// A fatal problem; the requested command could not be run
+    Error,
+}
+
+impl Severity {
+    fn label(&self) -> &'static str {
+        match *self {
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// A structured diagnostic for an invalid invocation, laid out the way
+/// annotate-snippets renders a compiler error: a title line, a ` --> `
+/// location line, the offending invocation, a caret underline beneath the
+/// bad token, and an optional `help:` footer. There is no `note:` footer.
+pub struct Diagnostic {
+    severity: Severity,
+    title: String,
+    invocation: Vec<String>,
+    bad_token: usize,
+    footer: Option<String>,
+}
+
+impl Diagnostic {
+    /// Create a new diagnostic pointing at `invocation[bad_token]`
+    pub fn new(severity: Severity, title: &str, invocation: Vec<String>, bad_token: usize) -> Diagnostic {
+        Diagnostic {
+            severity: severity,
+            title: title.to_string(),
+            invocation: invocation,
+            bad_token: bad_token,
+            footer: None,
+        }
+    }
+
+    /// Attach a footer suggestion, rendered as `= help: <footer>`
+    pub fn with_footer(mut self, footer: &str) -> Diagnostic {
+        self.footer = Some(footer.to_string());
+        self
+    }
+
+    /// Render the diagnostic, colorizing it if `color` allows it
+    pub fn render(&self, color: ColorChoice) -> String {
+        let colorize = color.enabled();
+        let argv = self.invocation.join(" ");
+
+        let title = format!("{}: {}", self.severity.label(), self.title);
+        let title = paint(&title, Red, colorize);
+
+        let underline = self.invocation
+            .iter()
+            .enumerate()
+            .map(|(index, token)| {
+                let width = token.chars().count();
+                if index == self.bad_token {
+                    "^".repeat(width)
+                } else {
+                    " ".repeat(width)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ");
+        let underline = paint(&underline, Red, colorize);
+
+        let mut out = String::with_capacity(250);
+        out.push_str(&title);
+        out.push_str("\n");
+        out.push_str(&format!(" --> {}\n", argv));
+        out.push_str(&format!("    {}\n", argv));
+        out.push_str(&format!("    {}\n", underline));
+
+        if let Some(ref footer) = self.footer {
+            out.push_str(&paint(&format!("    = help: {}", footer), Cyan, colorize));
+            out.push_str("\n");
+        }
+
+        out
+    }
+}
+
+fn paint(text: &str, colour: ::ansi_term::Colour, colorize: bool) -> String {
+    if colorize {
+        colour.paint(text).to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_no_color() {
+        let invocation = vec!["bin".to_string(), "buidl".to_string()];
+        let diag = Diagnostic::new(Severity::Error, "no such subcommand", invocation, 1)
+            .with_footer("a similar subcommand exists: 'build'");
+
+        let out = diag.render(ColorChoice::Never);
+
+        assert!(out.contains("error: no such subcommand"));
+        assert!(out.contains(" --> bin buidl"));
+        assert!(out.contains("    bin buidl"));
+        assert!(out.contains("        ^^^^^"));
+        assert!(out.contains("    = help: a similar subcommand exists: 'build'"));
+    }
+
+    #[test]
+    fn test_color_choice_never() {
+        assert_eq!(ColorChoice::Never.enabled(), false);
+    }
+
+    #[test]
+    fn test_color_choice_always() {
+        assert_eq!(ColorChoice::Always.enabled(), true);
+    }
+}
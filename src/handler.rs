@@ -12,6 +12,12 @@ use Command;
 use Message;
 use CmdWrapper;
 use CmdResult;
+use Shell;
+use ColorChoice;
+use completion;
+use man;
+use command_label;
+use diagnostic::{Diagnostic, Severity};
 
 use std::env;
 use std::io::Write;
@@ -34,9 +40,11 @@ use strsim::damerau_levenshtein;
 /// ```
 pub struct CmdHandler<'a> {
     description: Option<&'a str>,
+    version: Option<&'a str>,
     commands: Vec<Box<Command>>,
     program_name: String,
     args: Vec<String>,
+    color: ColorChoice,
 }
 
 impl<'a> CmdHandler<'a> {
@@ -47,18 +55,32 @@ impl<'a> CmdHandler<'a> {
 
         CmdHandler {
             description: None,
+            version: None,
             commands: Vec::new(),
             program_name: program_name,
             args: args,
+            color: ColorChoice::Auto,
         }
     }
 
+    /// Set whether diagnostics should be colorized
+    pub fn set_color_choice(mut self, color: ColorChoice) -> CmdHandler<'a> {
+        self.color = color;
+        self
+    }
+
     /// Set a one line description, used in `bin --help`
     pub fn set_description<'b>(mut self, descr: &'a str) -> CmdHandler<'a> {
         self.description = Some(descr);
         self
     }
 
+    /// Set the version string printed by `-V`/`--version`
+    pub fn set_version<'b>(mut self, version: &'a str) -> CmdHandler<'a> {
+        self.version = Some(version);
+        self
+    }
+
     /// Override default args
     pub fn override_args(mut self, args: Vec<String>) -> CmdHandler<'a> {
         self.args = args;
@@ -71,6 +93,21 @@ impl<'a> CmdHandler<'a> {
         self
     }
 
+    /// Generate a completion script for `shell`, listing the registered
+    /// subcommands
+    pub fn generate_completions(&self, shell: Shell) -> String {
+        completion::generate(&self.program_name, &self.commands, shell)
+    }
+
+    /// Generate a man page (troff source) describing the program and its
+    /// registered subcommands
+    pub fn generate_manpage(&self) -> String {
+        man::generate(&self.program_name,
+                      self.description,
+                      &self.short_usage(),
+                      &self.commands)
+    }
+
     fn short_usage(&self) -> String {
         let mut usage = String::with_capacity(150);
         usage.push_str("Usage:\n");
@@ -97,7 +134,7 @@ impl<'a> CmdHandler<'a> {
 
         let mut tw = TabWriter::new(Vec::new());
         for cmd in self.commands.iter() {
-            write!(&mut tw, "    {}\t{}\n", cmd.name(), cmd.description()).unwrap();
+            write!(&mut tw, "    {}\t{}\n", command_label(&**cmd), cmd.description()).unwrap();
         }
         tw.flush().unwrap();
 
@@ -110,9 +147,21 @@ impl<'a> CmdHandler<'a> {
         CmdResult::Help(msg)
     }
 
+    fn version(&self) -> CmdResult {
+        let mut msg = Message::new();
+
+        match self.version {
+            Some(version) => msg.add_line(&format!("{} {}", self.program_name, version)),
+            None => msg.add_line(&self.program_name),
+        }
+
+        CmdResult::Version(msg)
+    }
+
     fn bad_usage(&self) -> CmdResult {
         let mut msg = Message::new();
         msg.set_error(true);
+        msg.set_formated(self.color.enabled());
 
         msg.add_line("Invalid arguments.");
         msg.add_line(&self.short_usage());
@@ -128,6 +177,7 @@ impl<'a> CmdHandler<'a> {
         opts.parsing_style(ParsingStyle::StopAtFirstFree);
 
         opts.optflag("h", "help", "print this help menu");
+        opts.optflag("V", "version", "print version information");
 
         // args[0] is the program name
         let matches = match opts.parse(&self.args[1..]) {
@@ -138,6 +188,15 @@ impl<'a> CmdHandler<'a> {
             }
         };
 
+        // Catch a -V/--version request
+        if matches.opt_present("V") {
+            // -V/--version don't allow other options/args
+            if matches.free.len() != 0 {
+                return self.bad_usage();
+            }
+            return self.version();
+        }
+
         // Catch a -h/--help request
         if matches.opt_present("h") {
             // -h/--help don't allow other options/args
@@ -154,66 +213,118 @@ impl<'a> CmdHandler<'a> {
             return self.bad_usage();
         };
 
-        // Try to find the command
+        // Try to find the command, matching its name or any of its aliases
         for index in 0..self.commands.len() {
-            if self.commands[index].name() == command {
-                let wrap = CmdWrapper::new(self.commands.remove(index), self.args);
-                return CmdResult::Cmd(wrap);
+            if matches_name(&*self.commands[index], &command) {
+                let cmd = self.commands.remove(index);
+                let rest = matches.free[1..].to_vec();
+                let color = self.color;
+                return resolve_command(cmd, &rest, self.args, color);
             }
         }
 
         // Check built-in command
-        if (command == "help") && (matches.free.len() == 2) {
-            return self.help_for_command(&matches.free[1]);
+        if (command == "help") && (matches.free.len() >= 2) {
+            return self.help_for_command(&matches.free[1..]);
         }
 
 
-        // No command found, check for similariy
+        // No command found, check for similariy against names and aliases
         let mut sim_cmd: Option<&Box<Command>> = None;
         // We only want command with a similarity lowest than 3
         let mut lowest_sim: usize = 3;
         for cmd in self.commands.iter() {
-            let new_sim = damerau_levenshtein(cmd.name(), &command);
-            if new_sim < lowest_sim {
-                lowest_sim = new_sim;
-                sim_cmd = Some(cmd);
+            let mut candidates: Vec<&str> = vec![cmd.name()];
+            candidates.extend(cmd.aliases());
+            for candidate in candidates {
+                let new_sim = damerau_levenshtein(candidate, &command);
+                if new_sim < lowest_sim {
+                    lowest_sim = new_sim;
+                    sim_cmd = Some(cmd);
+                }
             }
         }
 
+        let bad_token = self.args
+            .iter()
+            .position(|arg| *arg == command)
+            .unwrap_or(self.args.len().saturating_sub(1));
+
         match sim_cmd {
             Some(cmd) => {
+                let diag = Diagnostic::new(Severity::Error,
+                                           "no such subcommand",
+                                           self.args.clone(),
+                                           bad_token)
+                    .with_footer(&format!("a similar subcommand exists: '{}'", cmd.name()));
+
                 let mut msg = Message::new();
                 msg.set_error(true);
-                msg.add_line("No such subcommand\n");
-                msg.add_line(&format!("    Did you mean `{}`?", cmd.name()));
+                msg.set_formated(false);
+                msg.add_line(&diag.render(self.color));
                 return CmdResult::BadUsage(msg);
             }
             None => {}
         };
 
+        let diag = Diagnostic::new(Severity::Error,
+                                   "no such subcommand",
+                                   self.args.clone(),
+                                   bad_token);
+
         let mut msg = Message::new();
         msg.set_error(true);
-        msg.add_line("No such subcommand");
+        msg.set_formated(false);
+        msg.add_line(&diag.render(self.color));
 
         CmdResult::UnknowCmd(msg)
     }
 
     /// Parse and run the requested command
-    pub fn run(self) {
+    pub fn run(self) -> i32 {
+        let color = self.color;
         match self.parse() {
-            CmdResult::Help(msg) => msg.print(),
-            CmdResult::HelpForCmd(cmd) => cmd.print_help(),
-            CmdResult::BadUsage(msg) => msg.print(),
-            CmdResult::UnknowCmd(msg) => msg.print(),
-            CmdResult::Cmd(cmd) => cmd.run(),
+            CmdResult::Help(msg) => {
+                msg.print();
+                0
+            }
+            CmdResult::HelpForCmd(cmd) => {
+                cmd.print_help();
+                0
+            }
+            CmdResult::BadUsage(msg) => {
+                msg.print();
+                2
+            }
+            CmdResult::UnknowCmd(msg) => {
+                msg.print();
+                2
+            }
+            CmdResult::Version(msg) => {
+                msg.print();
+                0
+            }
+            CmdResult::Cmd(cmd) => {
+                match cmd.run() {
+                    Ok(()) => 0,
+                    Err(err) => {
+                        let mut msg = Message::new();
+                        msg.set_error(true);
+                        msg.set_formated(color.enabled());
+                        msg.add_line(&format!("{}", err));
+                        msg.print();
+                        1
+                    }
+                }
+            }
         }
     }
 
-    fn help_for_command(&mut self, name: &str) -> CmdResult {
+    fn help_for_command(&mut self, names: &[String]) -> CmdResult {
         for index in 0..self.commands.len() {
-            if self.commands[index].name() == name {
-                let wrap = CmdWrapper::new(self.commands.remove(index), self.args.clone());
-                return CmdResult::HelpForCmd(wrap);
+            if matches_name(&*self.commands[index], &names[0]) {
+                let cmd = self.commands.remove(index);
+                return help_for_nested(cmd, &names[1..], self.args.clone(), self.color);
             };
         }
 
@@ -221,6 +332,84 @@ impl<'a> CmdHandler<'a> {
     }
 }
 
+/// Return true if `token` matches `cmd`'s name or one of its aliases
+fn matches_name(cmd: &Command, token: &str) -> bool {
+    cmd.name() == token || cmd.aliases().iter().any(|alias| *alias == token)
+}
+
+/// Resolve `free` against `cmd`'s children, recursing as long as a nested
+/// group keeps matching. Once `free` is exhausted, or `cmd` has no
+/// children, `cmd` itself is wrapped and returned.
+fn resolve_command(cmd: Box<Command>,
+                    free: &[String],
+                    args: Vec<String>,
+                    color: ColorChoice)
+                    -> CmdResult {
+    let children = cmd.subcommands();
+    if children.is_empty() || free.is_empty() {
+        return CmdResult::Cmd(CmdWrapper::new(cmd, args));
+    }
+
+    for child in children {
+        if matches_name(&*child, &free[0]) {
+            return resolve_command(child, &free[1..], args, color);
+        }
+    }
+
+    let bad_token = args
+        .iter()
+        .position(|arg| *arg == free[0])
+        .unwrap_or(args.len().saturating_sub(1));
+
+    let names: Vec<String> = cmd.subcommands()
+        .iter()
+        .map(|child| command_label(&**child))
+        .collect();
+    let diag = Diagnostic::new(Severity::Error, "no such subcommand", args.clone(), bad_token)
+        .with_footer(&format!("`{}` has the following subcommands: {}",
+                              cmd.name(),
+                              names.join(", ")));
+
+    let mut msg = Message::new();
+    msg.set_error(true);
+    msg.set_formated(false);
+    msg.add_line(&diag.render(color));
+
+    CmdResult::BadUsage(msg)
+}
+
+/// Resolve `names` against `cmd`'s children to find the command `help`
+/// should be printed for, recursing for `help parent child` invocations.
+fn help_for_nested(cmd: Box<Command>,
+                    names: &[String],
+                    args: Vec<String>,
+                    color: ColorChoice)
+                    -> CmdResult {
+    if names.is_empty() {
+        return CmdResult::HelpForCmd(CmdWrapper::new(cmd, args));
+    }
+
+    for child in cmd.subcommands() {
+        if matches_name(&*child, &names[0]) {
+            return help_for_nested(child, &names[1..], args, color);
+        }
+    }
+
+    let bad_token = args
+        .iter()
+        .position(|arg| *arg == names[0])
+        .unwrap_or(args.len().saturating_sub(1));
+
+    let diag = Diagnostic::new(Severity::Error, "no such subcommand", args.clone(), bad_token);
+
+    let mut msg = Message::new();
+    msg.set_error(true);
+    msg.set_formated(false);
+    msg.add_line(&diag.render(color));
+
+    CmdResult::BadUsage(msg)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -239,8 +428,8 @@ mod tests {
         fn description<'a>(&self) -> &'a str {
             "DESCR"
         }
-        fn run(&self, argv: &Vec<String>) {
-            // DO NOTHING
+        fn run(&self, argv: &Vec<String>) -> Result<(), Box<::std::error::Error>> {
+            Ok(())
         }
     }
 
@@ -256,8 +445,8 @@ mod tests {
         fn description<'a>(&self) -> &'a str {
             "DESCR another"
         }
-        fn run(&self, argv: &Vec<String>) {
-            // DO NOTHING
+        fn run(&self, argv: &Vec<String>) -> Result<(), Box<::std::error::Error>> {
+            Ok(())
         }
     }
 
@@ -313,11 +502,227 @@ mod tests {
             .add(Box::new(AnotherCmd));
 
         match handler.parse() {
-            CmdResult::UnknowCmd(msg) => assert!(msg.get().contains("No such subcommand")),
+            CmdResult::UnknowCmd(msg) => assert!(msg.get().contains("no such subcommand")),
+            _ => unreachable!(),
+        }
+    }
+
+    struct CmdWithAlias;
+
+    impl Command for CmdWithAlias {
+        fn name<'a>(&self) -> &'a str {
+            "checkout"
+        }
+        fn help<'a>(&self) -> &'a str {
+            "HELP checkout"
+        }
+        fn description<'a>(&self) -> &'a str {
+            "DESCR checkout"
+        }
+        fn run(&self, argv: &Vec<String>) -> Result<(), Box<::std::error::Error>> {
+            Ok(())
+        }
+        fn aliases(&self) -> &[&str] {
+            &["co"]
+        }
+    }
+
+    struct CmdChild;
+
+    impl Command for CmdChild {
+        fn name<'a>(&self) -> &'a str {
+            "add"
+        }
+        fn help<'a>(&self) -> &'a str {
+            "HELP child"
+        }
+        fn description<'a>(&self) -> &'a str {
+            "DESCR child"
+        }
+        fn run(&self, argv: &Vec<String>) -> Result<(), Box<::std::error::Error>> {
+            Ok(())
+        }
+    }
+
+    struct CmdGroup;
+
+    impl Command for CmdGroup {
+        fn name<'a>(&self) -> &'a str {
+            "remote"
+        }
+        fn help<'a>(&self) -> &'a str {
+            "HELP remote"
+        }
+        fn description<'a>(&self) -> &'a str {
+            "DESCR remote"
+        }
+        fn run(&self, argv: &Vec<String>) -> Result<(), Box<::std::error::Error>> {
+            Ok(())
+        }
+        fn subcommands(&self) -> Vec<Box<Command>> {
+            vec![Box::new(CmdChild)]
+        }
+    }
+
+    #[test]
+    fn test_cmd_alias() {
+        let args: Vec<String> = vec!["bin".to_string(), "co".to_string()];
+
+        let handler = CmdHandler::new().override_args(args).add(Box::new(CmdWithAlias));
+
+        match handler.parse() {
+            CmdResult::Cmd(cmd) => assert_eq!(cmd.name(), "checkout"),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_alias_typo_suggests_canonical_name() {
+        let args: Vec<String> = vec!["bin".to_string(), "c".to_string()];
+
+        let handler = CmdHandler::new().override_args(args).add(Box::new(CmdWithAlias));
+
+        match handler.parse() {
+            CmdResult::BadUsage(msg) => assert!(msg.get().contains("checkout")),
             _ => unreachable!(),
         }
     }
 
+    #[test]
+    fn test_help_lists_aliases() {
+        let args: Vec<String> = vec!["bin".to_string(), "-h".to_string()];
+
+        let handler = CmdHandler::new().override_args(args).add(Box::new(CmdWithAlias));
+
+        match handler.parse() {
+            CmdResult::Help(msg) => assert!(msg.get().contains("checkout (co)")),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_nested_cmd() {
+        let args: Vec<String> =
+            vec!["bin".to_string(), "remote".to_string(), "add".to_string()];
+
+        let handler = CmdHandler::new().override_args(args).add(Box::new(CmdGroup));
+
+        match handler.parse() {
+            CmdResult::Cmd(cmd) => assert_eq!(cmd.name(), "add"),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_nested_cmd_group_alone() {
+        let args: Vec<String> = vec!["bin".to_string(), "remote".to_string()];
+
+        let handler = CmdHandler::new().override_args(args).add(Box::new(CmdGroup));
+
+        match handler.parse() {
+            CmdResult::Cmd(cmd) => assert_eq!(cmd.name(), "remote"),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_nested_cmd_bad_child() {
+        let args: Vec<String> =
+            vec!["bin".to_string(), "remote".to_string(), "bogus".to_string()];
+
+        let handler = CmdHandler::new().override_args(args).add(Box::new(CmdGroup));
+
+        match handler.parse() {
+            CmdResult::BadUsage(msg) => assert!(msg.get().contains("add")),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_nested_help() {
+        let args: Vec<String> = vec!["bin".to_string(),
+                                      "help".to_string(),
+                                      "remote".to_string(),
+                                      "add".to_string()];
+
+        let handler = CmdHandler::new().override_args(args).add(Box::new(CmdGroup));
+
+        match handler.parse() {
+            CmdResult::HelpForCmd(cmd) => assert_eq!(cmd.name(), "add"),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_unknow_cmd_diagnostic() {
+        let args: Vec<String> = vec!["bin".to_string(), "bbbbbbbbbbb".to_string()];
+
+        let handler = CmdHandler::new()
+            .set_color_choice(ColorChoice::Never)
+            .override_args(args)
+            .add(Box::new(CmdA));
+
+        match handler.parse() {
+            CmdResult::UnknowCmd(msg) => {
+                assert!(msg.get().contains("error: no such subcommand"));
+                assert!(msg.get().contains(" --> bin bbbbbbbbbbb"));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_bad_command_diagnostic_footer() {
+        let args: Vec<String> = vec!["bin".to_string(), "cmd-b".to_string()];
+
+        let handler = CmdHandler::new()
+            .set_color_choice(ColorChoice::Never)
+            .override_args(args)
+            .add(Box::new(CmdA))
+            .add(Box::new(AnotherCmd));
+
+        match handler.parse() {
+            CmdResult::BadUsage(msg) => {
+                assert!(msg.get().contains("= help: a similar subcommand exists: 'cmd-a'"));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_version() {
+        let args: Vec<String> = vec!["bin".to_string(), "-V".to_string()];
+
+        match CmdHandler::new().set_version("1.2.3").override_args(args).parse() {
+            CmdResult::Version(msg) => assert!(msg.get().contains("1.2.3")),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_generate_completions() {
+        let handler = CmdHandler::new()
+            .add(Box::new(CmdA))
+            .add(Box::new(AnotherCmd));
+
+        let out = handler.generate_completions(Shell::Bash);
+        assert!(out.contains("cmd-a"));
+        assert!(out.contains("another-cmd"));
+    }
+
+    #[test]
+    fn test_generate_manpage() {
+        let handler = CmdHandler::new()
+            .set_description("A sample program")
+            .add(Box::new(CmdA))
+            .add(Box::new(AnotherCmd));
+
+        let out = handler.generate_manpage();
+        assert!(out.contains(".TH"));
+        assert!(out.contains(".SH COMMANDS"));
+        assert!(out.contains("cmd\\-a"));
+    }
+
     #[test]
     fn test_cmd() {
         let args: Vec<String> = vec!["bin".to_string(), "cmd-a".to_string()];
@@ -348,4 +753,45 @@ mod tests {
         }
     }
 
+    struct CmdFail;
+
+    impl Command for CmdFail {
+        fn name<'a>(&self) -> &'a str {
+            "fail"
+        }
+        fn help<'a>(&self) -> &'a str {
+            "HELP fail"
+        }
+        fn description<'a>(&self) -> &'a str {
+            "DESCR fail"
+        }
+        fn run(&self, argv: &Vec<String>) -> Result<(), Box<::std::error::Error>> {
+            Err(From::from("something went wrong"))
+        }
+    }
+
+    #[test]
+    fn test_run_exit_code_success() {
+        let args: Vec<String> = vec!["bin".to_string(), "cmd-a".to_string()];
+
+        let code = CmdHandler::new().override_args(args).add(Box::new(CmdA)).run();
+        assert_eq!(code, 0);
+    }
+
+    #[test]
+    fn test_run_exit_code_unknow_cmd() {
+        let args: Vec<String> = vec!["bin".to_string(), "bbbbbbbbbbb".to_string()];
+
+        let code = CmdHandler::new().override_args(args).add(Box::new(CmdA)).run();
+        assert_eq!(code, 2);
+    }
+
+    #[test]
+    fn test_run_exit_code_command_error() {
+        let args: Vec<String> = vec!["bin".to_string(), "fail".to_string()];
+
+        let code = CmdHandler::new().override_args(args).add(Box::new(CmdFail)).run();
+        assert_eq!(code, 1);
+    }
+
 }
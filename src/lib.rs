@@ -22,7 +22,7 @@
 //! - `cargo build`
 //! - `cargo clean`
 //!
-//! ```
+//! ```no_run
 //! extern crate subcmd;
 //! use subcmd::CmdHandler;
 //! use subcmd::Command;
@@ -33,8 +33,9 @@
 //!     fn name<'a>(&self) -> &'a str {"build"}
 //!     fn help<'a>(&self) -> &'a str {"Usage: cargo build [options]"}
 //!     fn description<'a>(&self) -> &'a str { "Compile the current project" }
-//!     fn run(&self, argv: &Vec<String>) {
+//!     fn run(&self, argv: &Vec<String>) -> Result<(), Box<::std::error::Error>> {
 //!         println!("I'm building your files");
+//!         Ok(())
 //!     }
 //! }
 //!
@@ -44,16 +45,17 @@
 //!     fn name<'a>(&self) -> &'a str {"clean"}
 //!     fn help<'a>(&self) -> &'a str {"Usage: cargo clean [options]"}
 //!     fn description<'a>(&self) -> &'a str { "Remove the target directory" }
-//!     fn run(&self, argv: &Vec<String>) {
+//!     fn run(&self, argv: &Vec<String>) -> Result<(), Box<::std::error::Error>> {
 //!         println!("I'm cleaning your files");
+//!         Ok(())
 //!     }
 //! }
 //!
 //! fn main() {
-//!     let mut handler = CmdHandler::new();
-//!     handler.add(Box::new(CmdBuild));
-//!     handler.add(Box::new(CmdClean));
-//!     handler.parse();
+//!     let handler = CmdHandler::new()
+//!         .add(Box::new(CmdBuild))
+//!         .add(Box::new(CmdClean));
+//!     ::std::process::exit(handler.run());
 //! }
 //! ```
 
@@ -74,6 +76,39 @@ pub use result::CmdResult;
 mod wrapper;
 pub use wrapper::CmdWrapper;
 
+mod completion;
+pub use completion::Shell;
+
+mod man;
+
+mod diagnostic;
+pub use diagnostic::ColorChoice;
+
+/// Build a `CmdHandler` with the compiling crate's version and
+/// description pulled from its Cargo metadata, so callers don't have to
+/// hand-copy their package version into the CLI. The program name still
+/// comes from `argv[0]`.
+///
+/// # Example
+///
+/// ```ignore
+/// #[macro_use]
+/// extern crate subcmd;
+///
+/// fn main() {
+///     let handler = subcmd_app!().add(Box::new(CmdBuild));
+///     handler.run();
+/// }
+/// ```
+#[macro_export]
+macro_rules! subcmd_app {
+    () => {
+        $crate::CmdHandler::new()
+            .set_version(env!("CARGO_PKG_VERSION"))
+            .set_description(env!("CARGO_PKG_DESCRIPTION"))
+    };
+}
+
 /// This trait must be implemented for each subcommand
 pub trait Command {
     /// This fonction must return the command line, without space. Like
@@ -87,6 +122,36 @@ pub trait Command {
     fn description<'a>(&self) -> &'a str;
 
     /// Main entry point. argv contains all argument passed to the binary,
-    /// with the program name in argv[0]
-    fn run(&self, argv: &Vec<String>);
+    /// with the program name in argv[0]. Returning `Err` causes
+    /// `CmdHandler::run` to print the error and return a non-zero exit
+    /// code.
+    fn run(&self, argv: &Vec<String>) -> Result<(), Box<::std::error::Error>>;
+
+    /// Return alternative names this command can be invoked with, in
+    /// addition to `name()`. Used by the handler to resolve typos of an
+    /// alias to the canonical name, and shown in parentheses next to the
+    /// command in `bin --help`.
+    fn aliases(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Return the child subcommands of this command, if any. A command
+    /// with children acts as a group: the handler resolves the remaining
+    /// arguments against them instead of calling `run()` directly, the
+    /// same way `git remote add` or `cargo install --list` dispatch to a
+    /// nested command.
+    fn subcommands(&self) -> Vec<Box<Command>> {
+        vec![]
+    }
+}
+
+/// Format a command's name for display, appending its aliases in
+/// parentheses when it has any
+fn command_label(cmd: &Command) -> String {
+    let aliases = cmd.aliases();
+    if aliases.is_empty() {
+        cmd.name().to_string()
+    } else {
+        format!("{} ({})", cmd.name(), aliases.join(", "))
+    }
 }
@@ -0,0 +1,118 @@
+// Copyright © 2015-2016 - Samuel Dolt <samuel@dolt.ch>
+//
+// See the COPYRIGHT file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use Command;
+
+/// Escape backslashes and hyphens for troff output
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('-', "\\-")
+}
+
+/// Generate a man page for the whole program
+pub fn generate(program_name: &str,
+                 description: Option<&str>,
+                 short_usage: &str,
+                 commands: &[Box<Command>])
+                 -> String {
+    let mut out = String::with_capacity(500);
+
+    out.push_str(&format!(".TH {} 1\n", escape(program_name).to_uppercase()));
+    out.push_str(".SH NAME\n");
+    out.push_str(&format!("{}", escape(program_name)));
+    match description {
+        Some(descr) => out.push_str(&format!(" \\- {}\n", escape(descr))),
+        None => out.push_str("\n"),
+    }
+
+    out.push_str(".SH SYNOPSIS\n");
+    out.push_str(&format!("{}\n", escape(short_usage)));
+
+    if let Some(descr) = description {
+        out.push_str(".SH DESCRIPTION\n");
+        out.push_str(&format!("{}\n", escape(descr)));
+    }
+
+    out.push_str(".SH COMMANDS\n");
+    for cmd in commands.iter() {
+        out.push_str(&format!(".TP\n.B {}\n", escape(cmd.name())));
+        out.push_str(&format!(".IP\n{}\n", escape(cmd.description())));
+    }
+
+    out
+}
+
+/// Generate a man page for a single command
+pub fn generate_for_command(program_name: &str, cmd: &Command) -> String {
+    let mut out = String::with_capacity(250);
+
+    out.push_str(&format!(".TH {}-{} 1\n",
+                          escape(program_name).to_uppercase(),
+                          escape(cmd.name()).to_uppercase()));
+    out.push_str(".SH NAME\n");
+    out.push_str(&format!("{}-{} \\- {}\n",
+                          escape(program_name),
+                          escape(cmd.name()),
+                          escape(cmd.description())));
+
+    out.push_str(".SH SYNOPSIS\n");
+    out.push_str(&format!("{}\n", escape(cmd.help())));
+
+    out.push_str(".SH DESCRIPTION\n");
+    out.push_str(&format!("{}\n", escape(cmd.description())));
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Command;
+
+    struct CmdA;
+
+    impl Command for CmdA {
+        fn name<'a>(&self) -> &'a str {
+            "cmd-a"
+        }
+        fn help<'a>(&self) -> &'a str {
+            "Usage: bin cmd-a"
+        }
+        fn description<'a>(&self) -> &'a str {
+            "DESCR"
+        }
+        fn run(&self, argv: &Vec<String>) -> Result<(), Box<::std::error::Error>> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a-b\\c"), "a\\-b\\\\c");
+    }
+
+    #[test]
+    fn test_generate() {
+        let commands: Vec<Box<Command>> = vec![Box::new(CmdA)];
+        let out = generate("bin", Some("A sample program"), "bin <command>", &commands);
+
+        assert!(out.contains(".TH BIN 1"));
+        assert!(out.contains(".SH SYNOPSIS"));
+        assert!(out.contains(".SH COMMANDS"));
+        assert!(out.contains("cmd\\-a"));
+    }
+
+    #[test]
+    fn test_generate_for_command() {
+        let out = generate_for_command("bin", &CmdA);
+
+        assert!(out.contains(".TH BIN-CMD\\-A 1"));
+        assert!(out.contains(".SH DESCRIPTION"));
+    }
+}
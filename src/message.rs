@@ -75,6 +75,13 @@ impl Message {
     pub fn set_error(&mut self, state: bool) {
         self.is_error = state;
     }
+
+    /// Override whether `getf()` colorizes the message. Used by callers
+    /// that already embed their own coloring (e.g. a rendered
+    /// `Diagnostic`) and don't want it wrapped again.
+    pub fn set_formated(&mut self, state: bool) {
+        self.formated = state;
+    }
 }
 
 #[cfg(test)]
@@ -113,6 +120,17 @@ mod tests {
         assert_eq!(msg.get(), "Some new line\n");
     }
 
+    #[test]
+    fn test_message_set_formated() {
+        let mut msg = Message::new();
+
+        msg.set_formated(false);
+        assert_eq!(msg.is_formated(), false);
+
+        msg.set_formated(true);
+        assert_eq!(msg.is_formated(), true);
+    }
+
     #[test]
     fn test_message_set_error() {
         let mut msg = Message::new();
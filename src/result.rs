@@ -20,12 +20,13 @@ use CmdWrapper;
 /// use subcmd::CmdResult;
 ///
 /// let handler = CmdHandler::new();
-/// match handler.run() {
+/// match handler.parse() {
 ///     CmdResult::Help(msg)           => msg.print(),
 ///     CmdResult::HelpForCmd(cmd)     => cmd.print_help(),
 ///     CmdResult::BadUsage(msg)       => msg.print(),
 ///     CmdResult::UnknowCmd(msg)      => msg.print(),
-///     CmdResult::Cmd(cmd)            => cmd.run(),
+///     CmdResult::Version(msg)        => msg.print(),
+///     CmdResult::Cmd(cmd)            => { cmd.run(); },
 /// }
 /// ```
 pub enum CmdResult {
@@ -41,6 +42,9 @@ pub enum CmdResult {
     /// A unknow command like `unknow-command` has been requested
     UnknowCmd(Message),
 
+    /// Version information has been requested with `-V` or `--version`
+    Version(Message),
+
     /// A know command has been requested
     Cmd(CmdWrapper),
 }
@@ -10,6 +10,12 @@
 
 pub use Command;
 
+use man;
+use command_label;
+
+use std::io::Write;
+use tabwriter::TabWriter;
+
 /// This wrapper hold a command object and a arguments vectors.
 pub struct CmdWrapper {
     cmd: Box<Command>,
@@ -38,11 +44,30 @@ impl CmdWrapper {
     /// Print the help of the wrapper command
     pub fn print_help(&self) {
         println!("{}", self.cmd.help());
+
+        let children = self.cmd.subcommands();
+        if !children.is_empty() {
+            println!("\nSubcommands are:");
+
+            let mut tw = TabWriter::new(Vec::new());
+            for child in children.iter() {
+                write!(&mut tw, "    {}\t{}\n", command_label(&**child), child.description())
+                    .unwrap();
+            }
+            tw.flush().unwrap();
+
+            println!("{}", String::from_utf8(tw.unwrap()).unwrap());
+        }
+    }
+
+    /// Generate a man page (troff source) for the wrapped command
+    pub fn generate_manpage(&self) -> String {
+        man::generate_for_command(&self.args[0], &*self.cmd)
     }
 
     /// Run the command
-    pub fn run(&self) {
-        self.cmd.run(&self.args);
+    pub fn run(&self) -> Result<(), Box<::std::error::Error>> {
+        self.cmd.run(&self.args)
     }
 
     /// Return the embedded command
@@ -69,11 +94,12 @@ mod tests {
         fn description<'a>(&self) -> &'a str {
             "descr. for fake"
         }
-        fn run(&self, argv: &Vec<String>) {
+        fn run(&self, argv: &Vec<String>) -> Result<(), Box<::std::error::Error>> {
             unsafe {
                 FakeCmdRunCalled = true;
             }
             assert_eq!(argv[0], "test");
+            Ok(())
         }
     }
 
@@ -84,7 +110,7 @@ mod tests {
         assert_eq!(wrap.name(), "fake");
         assert_eq!(wrap.help(), "help for fake");
 
-        wrap.run();
+        assert!(wrap.run().is_ok());
         unsafe {
             assert_eq!(FakeCmdRunCalled, true);
         }
@@ -92,4 +118,13 @@ mod tests {
         let fake = wrap.unwrap();
         assert_eq!(fake.description(), "descr. for fake");
     }
+
+    #[test]
+    fn test_cmd_wrapper_generate_manpage() {
+        let wrap = CmdWrapper::new(Box::new(FakeCmd), vec!["test".to_string()]);
+
+        let out = wrap.generate_manpage();
+        assert!(out.contains(".TH TEST-FAKE 1"));
+        assert!(out.contains("descr. for fake"));
+    }
 }